@@ -0,0 +1,179 @@
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use uuid::Uuid;
+
+/// Decides how newly created objects get their IDs
+pub trait IdProvider: Send + Sync {
+    /// The ID for a new object, or `None` when the database assigns it on insert
+    fn next_id(&self) -> Option<u64>;
+}
+
+/// Random IDs taken from a UUIDv4. Needs no coordination, at the cost of a small chance of collision
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UuidIdProvider;
+
+impl IdProvider for UuidIdProvider {
+    fn next_id(&self) -> Option<u64> {
+        // Folding the halves together keeps all 64 bits random, where either half alone carries the version or variant bits
+        let (high, low) = Uuid::new_v4().as_u64_pair();
+        Some(high ^ low)
+    }
+}
+
+/// Leaves ID assignment to the database, for adapters that use database-generated keys
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DatabaseAssignedIds;
+
+impl IdProvider for DatabaseAssignedIds {
+    fn next_id(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// Time-ordered IDs made of a millisecond timestamp, a worker ID and a sequence number,
+/// so that multiple writers can create IDs without colliding
+#[derive(Debug)]
+pub struct SnowflakeIdProvider {
+    worker_id: u64,
+    clock: fn() -> u64,
+    // The last timestamp an ID was made for, and the sequence number used within it
+    state: Mutex<(u64, u64)>,
+}
+
+impl SnowflakeIdProvider {
+    /// Milliseconds since the Unix epoch that snowflake timestamps count from (2023-01-01)
+    pub const EPOCH: u64 = 1_672_531_200_000;
+    /// The largest worker ID that fits in a snowflake
+    pub const MAX_WORKER_ID: u16 = (1 << WORKER_BITS) - 1;
+
+    /// Creates a provider for the given worker
+    ///
+    /// # Panics
+    /// Panics if `worker_id` is greater than [`SnowflakeIdProvider::MAX_WORKER_ID`]
+    pub fn new(worker_id: u16) -> SnowflakeIdProvider {
+        Self::with_clock(worker_id, Self::now)
+    }
+
+    fn with_clock(worker_id: u16, clock: fn() -> u64) -> SnowflakeIdProvider {
+        assert!(
+            worker_id <= Self::MAX_WORKER_ID,
+            "snowflake worker IDs must be at most {}",
+            Self::MAX_WORKER_ID
+        );
+        SnowflakeIdProvider {
+            worker_id: worker_id as u64,
+            clock,
+            state: Mutex::new((0, 0)),
+        }
+    }
+
+    /// The worker ID this provider stamps into its IDs
+    pub fn worker_id(&self) -> u16 {
+        self.worker_id as u16
+    }
+
+    /// Milliseconds since [`SnowflakeIdProvider::EPOCH`], or 0 if the system clock is set before it
+    fn now() -> u64 {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis() as u64);
+        millis.saturating_sub(Self::EPOCH)
+    }
+}
+
+const WORKER_BITS: u32 = 10;
+const SEQUENCE_BITS: u32 = 12;
+const MAX_SEQUENCE: u64 = (1 << SEQUENCE_BITS) - 1;
+
+impl IdProvider for SnowflakeIdProvider {
+    fn next_id(&self) -> Option<u64> {
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let (last_timestamp, last_sequence) = *state;
+
+        // If the clock hasn't moved past the last timestamp, whether it stalled or stepped back, keep counting from the
+        // last timestamp instead, running it ahead of the clock once a millisecond's sequence numbers are used up
+        let now = (self.clock)();
+        let (timestamp, sequence) = if now > last_timestamp {
+            (now, 0)
+        } else if last_sequence < MAX_SEQUENCE {
+            (last_timestamp, last_sequence + 1)
+        } else {
+            (last_timestamp + 1, 0)
+        };
+        *state = (timestamp, sequence);
+
+        Some(
+            (timestamp << (WORKER_BITS + SEQUENCE_BITS))
+                | (self.worker_id << SEQUENCE_BITS)
+                | sequence,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    #[test]
+    fn database_assigned_defers() {
+        assert_eq!(DatabaseAssignedIds.next_id(), None);
+    }
+
+    #[test]
+    fn uuid_ids_are_distinct() {
+        let ids: HashSet<u64> = (0..1000)
+            .map(|_| UuidIdProvider.next_id().unwrap())
+            .collect();
+        assert_eq!(ids.len(), 1000);
+    }
+
+    #[test]
+    fn snowflakes_are_increasing_and_carry_worker() {
+        let provider = SnowflakeIdProvider::new(37);
+        let ids: Vec<u64> = (0..10_000).map(|_| provider.next_id().unwrap()).collect();
+
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(ids
+            .iter()
+            .all(|id| (id >> SEQUENCE_BITS) & SnowflakeIdProvider::MAX_WORKER_ID as u64 == 37));
+    }
+
+    #[test]
+    fn snowflakes_run_ahead_of_a_stuck_clock() {
+        // A clock set before the epoch always reads 0
+        let provider = SnowflakeIdProvider::with_clock(1, || 0);
+        let ids: Vec<u64> = (0..3 * (MAX_SEQUENCE + 1))
+            .map(|_| provider.next_id().unwrap())
+            .collect();
+
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(ids.last().unwrap() >> (WORKER_BITS + SEQUENCE_BITS), 3);
+    }
+
+    #[test]
+    fn snowflakes_survive_the_clock_stepping_back() {
+        static CLOCK: AtomicU64 = AtomicU64::new(1_000);
+        let provider = SnowflakeIdProvider::with_clock(1, || CLOCK.load(Ordering::SeqCst));
+
+        let mut ids = vec![provider.next_id().unwrap()];
+        CLOCK.store(10, Ordering::SeqCst);
+        ids.extend((0..2 * (MAX_SEQUENCE + 1)).map(|_| provider.next_id().unwrap()));
+        CLOCK.store(5_000, Ordering::SeqCst);
+        ids.push(provider.next_id().unwrap());
+
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(ids.last().unwrap() >> (WORKER_BITS + SEQUENCE_BITS), 5_000);
+    }
+
+    #[test]
+    #[should_panic]
+    fn snowflake_rejects_oversized_worker() {
+        SnowflakeIdProvider::new(SnowflakeIdProvider::MAX_WORKER_ID + 1);
+    }
+}
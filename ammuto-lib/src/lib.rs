@@ -1,3 +1,7 @@
+pub mod id;
+pub mod media;
+pub mod permissions;
+
 pub fn add(left: usize, right: usize) -> usize {
     left + right
 }
@@ -0,0 +1,516 @@
+/// The broad category a piece of media falls into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MediaKind {
+    Image,
+    Animation,
+    Video,
+    Audio,
+    Document,
+    Archive,
+    Other,
+}
+
+impl MediaKind {
+    /// Determines the kind of media from its leading bytes, falling back to the file extension when the bytes aren't recognised.
+    /// The extension is also used to tell apart what a generic container holds, such as an OOXML document in a ZIP
+    pub fn sniff(bytes: &[u8], extension: Option<&str>) -> MediaKind {
+        let extension = extension.map(normalise_extension);
+        let extension = extension.as_deref();
+        extension
+            .and_then(|extension| container_kind(bytes, extension))
+            .or_else(|| Self::from_magic(bytes))
+            .or_else(|| extension.and_then(Self::from_extension))
+            .unwrap_or(MediaKind::Other)
+    }
+
+    /// Determines the kind of media from its magic bytes alone
+    pub fn from_magic(bytes: &[u8]) -> Option<MediaKind> {
+        let kind = match bytes {
+            [0x89, b'P', b'N', b'G', ..] => {
+                if png_is_animated(bytes) {
+                    MediaKind::Animation
+                } else {
+                    MediaKind::Image
+                }
+            }
+            [0xFF, 0xD8, 0xFF, ..]
+            | [b'I', b'I', 0x2A, 0x00, ..]
+            | [b'M', b'M', 0x00, 0x2A, ..] => MediaKind::Image,
+            [b'B', b'M', _, _, _, _, 0, 0, 0, 0, _, _, _, _, d0, d1, d2, d3, ..]
+                if is_bmp_dib_header_size(u32::from_le_bytes([*d0, *d1, *d2, *d3])) =>
+            {
+                MediaKind::Image
+            }
+            [b'G', b'I', b'F', b'8', ..] => {
+                if gif_is_animated(bytes) {
+                    MediaKind::Animation
+                } else {
+                    MediaKind::Image
+                }
+            }
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', chunks @ ..] => {
+                match chunks {
+                    // Only the extended VP8X format can carry animation, flagged in its first byte
+                    [b'V', b'P', b'8', b'X', _, _, _, _, flags, ..] if flags & 0x02 != 0 => {
+                        MediaKind::Animation
+                    }
+                    _ => MediaKind::Image,
+                }
+            }
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => MediaKind::Audio,
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'A', b'V', b'I', b' ', ..] => MediaKind::Video,
+            [_, _, _, _, b'f', b't', b'y', b'p', b0, b1, b2, b3, ..] => {
+                return iso_brand_kind(&[*b0, *b1, *b2, *b3])
+            }
+            [0x1A, 0x45, 0xDF, 0xA3, ..] => MediaKind::Video,
+            [b'I', b'D', b'3', ..]
+            | [0xFF, 0xFB, ..]
+            | [0xFF, 0xF3, ..]
+            | [0xFF, 0xF2, ..]
+            | [b'f', b'L', b'a', b'C', ..]
+            | [b'O', b'g', b'g', b'S', ..] => MediaKind::Audio,
+            [b'%', b'P', b'D', b'F', ..] => MediaKind::Document,
+            [b'P', b'K', 0x03, 0x04, ..] => zip_kind(bytes),
+            [0x1F, 0x8B, ..]
+            | [b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C, ..]
+            | [b'R', b'a', b'r', b'!', ..] => MediaKind::Archive,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    /// Determines the kind of media from a file extension, ignoring case and any leading dot
+    pub fn from_extension(extension: &str) -> Option<MediaKind> {
+        let kind = match normalise_extension(extension).as_str() {
+            "png" | "jpg" | "jpeg" | "bmp" | "tif" | "tiff" | "webp" | "avif" | "heic" | "heif"
+            | "svg" => MediaKind::Image,
+            "gif" | "apng" => MediaKind::Animation,
+            "mp4" | "m4v" | "mkv" | "webm" | "mov" | "avi" | "wmv" | "flv" | "ogv" | "ogm" => {
+                MediaKind::Video
+            }
+            "mp3" | "m4a" | "mka" | "flac" | "ogg" | "oga" | "opus" | "wav" | "aac" => {
+                MediaKind::Audio
+            }
+            "pdf" | "txt" | "md" | "doc" => MediaKind::Document,
+            extension if ZIP_DOCUMENT_EXTENSIONS.contains(&extension) => MediaKind::Document,
+            "zip" | "cbz" | "rar" | "cbr" | "7z" | "gz" | "tar" => MediaKind::Archive,
+            _ => return None,
+        };
+        Some(kind)
+    }
+}
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const EBML_MAGIC: &[u8] = b"\x1A\x45\xDF\xA3";
+const OGG_MAGIC: &[u8] = b"OggS";
+
+/// Document formats stored as ZIP containers
+const ZIP_DOCUMENT_EXTENSIONS: &[&str] = &["docx", "xlsx", "pptx", "odt", "ods", "odp", "epub"];
+
+fn normalise_extension(extension: &str) -> String {
+    extension.trim_start_matches('.').to_ascii_lowercase()
+}
+
+/// Refines a generic container using its extension, where the magic bytes alone can't tell what the container holds
+fn container_kind(bytes: &[u8], extension: &str) -> Option<MediaKind> {
+    // OOXML has no mimetype entry, so a document extension is the only sign a ZIP is a document
+    if bytes.starts_with(ZIP_MAGIC) && ZIP_DOCUMENT_EXTENSIONS.contains(&extension) {
+        return Some(MediaKind::Document);
+    }
+    // Matroska and Ogg hold audio or video alike, and default to the more common of the two
+    if bytes.starts_with(EBML_MAGIC) && extension == "mka" {
+        return Some(MediaKind::Audio);
+    }
+    if bytes.starts_with(OGG_MAGIC) && matches!(extension, "ogv" | "ogm") {
+        return Some(MediaKind::Video);
+    }
+    None
+}
+
+/// Walks the GIF blocks, treating a NETSCAPE2.0 looping extension or a second image as an animation
+fn gif_is_animated(bytes: &[u8]) -> bool {
+    let colour_table_size = |packed: u8| {
+        if packed & 0x80 != 0 {
+            3 << ((packed & 0x07) + 1)
+        } else {
+            0
+        }
+    };
+    let Some(&packed) = bytes.get(10) else {
+        return false;
+    };
+    // Header, logical screen descriptor and global colour table
+    let mut offset = 13 + colour_table_size(packed);
+    let mut images = 0;
+    loop {
+        match bytes.get(offset) {
+            Some(0x21) => {
+                let label = bytes.get(offset + 1);
+                let data = bytes.get(offset + 3..offset + 14);
+                if label == Some(&0xFF) && data == Some(b"NETSCAPE2.0") {
+                    return true;
+                }
+                match skip_sub_blocks(bytes, offset + 2) {
+                    Some(end) => offset = end,
+                    None => return false,
+                }
+            }
+            Some(0x2C) => {
+                images += 1;
+                if images > 1 {
+                    return true;
+                }
+                let Some(&packed) = bytes.get(offset + 9) else {
+                    return false;
+                };
+                // Descriptor, local colour table and the LZW minimum code size
+                let data = offset + 10 + colour_table_size(packed) + 1;
+                match skip_sub_blocks(bytes, data) {
+                    Some(end) => offset = end,
+                    None => return false,
+                }
+            }
+            _ => return false,
+        }
+    }
+}
+
+/// Skips a chain of GIF data sub-blocks, returning the offset after its terminator
+fn skip_sub_blocks(bytes: &[u8], mut offset: usize) -> Option<usize> {
+    loop {
+        let size = *bytes.get(offset)? as usize;
+        offset += 1;
+        if size == 0 {
+            return Some(offset);
+        }
+        offset += size;
+    }
+}
+
+/// Walks the PNG chunks up to the first IDAT, as an APNG must declare its acTL chunk before any image data
+fn png_is_animated(bytes: &[u8]) -> bool {
+    let mut offset = 8;
+    while let Some(header) = bytes.get(offset..).and_then(|rest| rest.get(..8)) {
+        match &header[4..8] {
+            b"acTL" => return true,
+            b"IDAT" | b"IEND" => return false,
+            _ => {}
+        }
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        // Length, type, data and CRC
+        offset = offset.saturating_add(12).saturating_add(length);
+    }
+    false
+}
+
+fn is_bmp_dib_header_size(size: u32) -> bool {
+    matches!(size, 12 | 16 | 40 | 52 | 56 | 64 | 108 | 124)
+}
+
+/// Maps the major brand of an ISO base media file to a kind, leaving unknown brands to the extension
+fn iso_brand_kind(brand: &[u8; 4]) -> Option<MediaKind> {
+    let kind = match brand {
+        b"avif" | b"heic" | b"heix" | b"heim" | b"heis" | b"mif1" => MediaKind::Image,
+        b"avis" | b"msf1" | b"hevc" | b"hevx" => MediaKind::Animation,
+        b"M4A " | b"M4B " | b"M4P " | b"F4A " | b"F4B " => MediaKind::Audio,
+        b"isom" | b"iso2" | b"iso4" | b"iso5" | b"iso6" | b"mp41" | b"mp42" | b"avc1" | b"dash"
+        | b"M4V " | b"F4V " | b"qt  " | b"3gp4" | b"3gp5" | b"3gp6" | b"3g2a" | b"mmp4"
+        | b"MSNV" => MediaKind::Video,
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// Checks for the uncompressed `mimetype` entry that EPUB and OpenDocument files start with
+fn zip_kind(bytes: &[u8]) -> MediaKind {
+    let read_u16 = |offset: usize| {
+        bytes
+            .get(offset..offset + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+    };
+    let mimetype = (|| {
+        let method = read_u16(8)?;
+        let name_length = read_u16(26)?;
+        let extra_length = read_u16(28)?;
+        let name = bytes.get(30..30 + name_length)?;
+        if method != 0 || name != b"mimetype" {
+            return None;
+        }
+        let size = bytes.get(18..22)?;
+        let size = u32::from_le_bytes([size[0], size[1], size[2], size[3]]) as usize;
+        let start = 30 + name_length + extra_length;
+        bytes.get(start..start.checked_add(size)?)
+    })();
+
+    match mimetype {
+        Some(b"application/epub+zip") => MediaKind::Document,
+        Some(mimetype) if mimetype.starts_with(b"application/vnd.oasis.opendocument.") => {
+            MediaKind::Document
+        }
+        _ => MediaKind::Archive,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+        let mut bytes = b"\x89PNG\r\n\x1a\n".to_vec();
+        for (kind, data) in chunks {
+            bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(*kind);
+            bytes.extend_from_slice(data);
+            bytes.extend_from_slice(&[0; 4]);
+        }
+        bytes
+    }
+
+    fn riff(form: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut bytes = b"RIFF".to_vec();
+        bytes.extend_from_slice(&(body.len() as u32 + 4).to_le_bytes());
+        bytes.extend_from_slice(form);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    fn zip_with_stored_entry(name: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut bytes = ZIP_MAGIC.to_vec();
+        bytes.extend_from_slice(&[20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+        bytes.extend_from_slice(name);
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    fn gif(blocks: &[&[u8]]) -> Vec<u8> {
+        // 1x1 screen with a two colour global table
+        let mut bytes = b"GIF89a\x01\0\x01\0\x80\0\0".to_vec();
+        bytes.extend_from_slice(&[0; 6]);
+        for block in blocks {
+            bytes.extend_from_slice(block);
+        }
+        bytes.push(0x3B);
+        bytes
+    }
+
+    const GIF_IMAGE: &[u8] = b"\x2C\0\0\0\0\x01\0\x01\0\0\x02\x02\x44\x01\0";
+    const GIF_COMMENT: &[u8] = b"\x21\xFE\x05hello\0";
+    const GIF_LOOP: &[u8] = b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\0\0\0";
+
+    fn ftyp(brand: &[u8; 4]) -> Vec<u8> {
+        let mut bytes = b"\0\0\0\x18ftyp".to_vec();
+        bytes.extend_from_slice(brand);
+        bytes.extend_from_slice(&[0; 12]);
+        bytes
+    }
+
+    #[test]
+    fn sniffs_magic_bytes() {
+        assert_eq!(
+            MediaKind::sniff(b"\xFF\xD8\xFF\xE0", None),
+            MediaKind::Image
+        );
+        assert_eq!(MediaKind::sniff(b"ID3\x04", None), MediaKind::Audio);
+        assert_eq!(
+            MediaKind::sniff(b"\xFF\xFB\x90\x64", None),
+            MediaKind::Audio
+        );
+        assert_eq!(MediaKind::sniff(b"fLaC", None), MediaKind::Audio);
+        assert_eq!(
+            MediaKind::sniff(b"\x1A\x45\xDF\xA3\x01", None),
+            MediaKind::Video
+        );
+        assert_eq!(MediaKind::sniff(b"%PDF-1.7", None), MediaKind::Document);
+        assert_eq!(MediaKind::sniff(b"\x1F\x8B\x08", None), MediaKind::Archive);
+        assert_eq!(
+            MediaKind::sniff(b"7z\xBC\xAF\x27\x1C", None),
+            MediaKind::Archive
+        );
+        assert_eq!(MediaKind::sniff(b"Rar!\x1A\x07", None), MediaKind::Archive);
+    }
+
+    #[test]
+    fn png_animation_requires_actl_before_idat() {
+        let still = png(&[(b"IHDR", &[0; 13]), (b"IDAT", b"..acTL..")]);
+        assert_eq!(MediaKind::sniff(&still, None), MediaKind::Image);
+
+        let trailing = png(&[(b"IHDR", &[0; 13]), (b"IDAT", b"data"), (b"acTL", &[0; 8])]);
+        assert_eq!(MediaKind::sniff(&trailing, None), MediaKind::Image);
+
+        let animated = png(&[(b"IHDR", &[0; 13]), (b"acTL", &[0; 8]), (b"IDAT", b"data")]);
+        assert_eq!(MediaKind::sniff(&animated, None), MediaKind::Animation);
+    }
+
+    #[test]
+    fn gif_animation() {
+        assert_eq!(
+            MediaKind::sniff(&gif(&[GIF_COMMENT, GIF_IMAGE]), None),
+            MediaKind::Image
+        );
+        assert_eq!(
+            MediaKind::sniff(&gif(&[GIF_LOOP, GIF_IMAGE]), None),
+            MediaKind::Animation
+        );
+        assert_eq!(
+            MediaKind::sniff(&gif(&[GIF_IMAGE, GIF_COMMENT, GIF_IMAGE]), None),
+            MediaKind::Animation
+        );
+        assert_eq!(MediaKind::sniff(b"GIF89a", None), MediaKind::Image);
+    }
+
+    #[test]
+    fn riff_forms() {
+        let mut vp8x = b"VP8X\x0A\0\0\0".to_vec();
+        vp8x.extend_from_slice(&[0x02, 0, 0, 0]);
+        assert_eq!(
+            MediaKind::sniff(&riff(b"WEBP", &vp8x), None),
+            MediaKind::Animation
+        );
+
+        vp8x[8] = 0x10;
+        assert_eq!(
+            MediaKind::sniff(&riff(b"WEBP", &vp8x), None),
+            MediaKind::Image
+        );
+        assert_eq!(
+            MediaKind::sniff(&riff(b"WEBP", b"VP8 \x08\0\0\0..ANIM.."), None),
+            MediaKind::Image
+        );
+
+        assert_eq!(
+            MediaKind::sniff(&riff(b"WAVE", b"fmt "), None),
+            MediaKind::Audio
+        );
+        assert_eq!(
+            MediaKind::sniff(&riff(b"AVI ", b"LIST"), None),
+            MediaKind::Video
+        );
+    }
+
+    #[test]
+    fn iso_brands() {
+        assert_eq!(MediaKind::sniff(&ftyp(b"isom"), None), MediaKind::Video);
+        assert_eq!(MediaKind::sniff(&ftyp(b"qt  "), None), MediaKind::Video);
+        assert_eq!(MediaKind::sniff(&ftyp(b"avif"), None), MediaKind::Image);
+        assert_eq!(MediaKind::sniff(&ftyp(b"heix"), None), MediaKind::Image);
+        assert_eq!(MediaKind::sniff(&ftyp(b"avis"), None), MediaKind::Animation);
+        assert_eq!(MediaKind::sniff(&ftyp(b"M4B "), None), MediaKind::Audio);
+        assert_eq!(MediaKind::sniff(&ftyp(b"zzzz"), None), MediaKind::Other);
+        assert_eq!(
+            MediaKind::sniff(&ftyp(b"zzzz"), Some("m4a")),
+            MediaKind::Audio
+        );
+    }
+
+    #[test]
+    fn bmp_requires_a_valid_header() {
+        let mut bmp = b"BM\x46\0\0\0\0\0\0\0\x36\0\0\0".to_vec();
+        bmp.extend_from_slice(&40u32.to_le_bytes());
+        assert_eq!(MediaKind::sniff(&bmp, None), MediaKind::Image);
+
+        assert_eq!(
+            MediaKind::sniff(b"BMW owners notes", Some("txt")),
+            MediaKind::Document
+        );
+    }
+
+    #[test]
+    fn zip_containers() {
+        assert_eq!(
+            MediaKind::sniff(&zip_with_stored_entry(b"file.txt", b"hi"), None),
+            MediaKind::Archive
+        );
+        assert_eq!(
+            MediaKind::sniff(&zip_with_stored_entry(b"page1.png", b""), Some("cbz")),
+            MediaKind::Archive
+        );
+        assert_eq!(
+            MediaKind::sniff(
+                &zip_with_stored_entry(b"[Content_Types].xml", b"<Types/>"),
+                Some("docx")
+            ),
+            MediaKind::Document
+        );
+        assert_eq!(
+            MediaKind::sniff(
+                &zip_with_stored_entry(b"mimetype", b"application/epub+zip"),
+                None
+            ),
+            MediaKind::Document
+        );
+        assert_eq!(
+            MediaKind::sniff(
+                &zip_with_stored_entry(b"mimetype", b"application/vnd.oasis.opendocument.text"),
+                Some("zip")
+            ),
+            MediaKind::Document
+        );
+        for extension in ["xlsx", "pptx", "ods", "odp"] {
+            assert_eq!(
+                MediaKind::sniff(
+                    &zip_with_stored_entry(b"[Content_Types].xml", b"<Types/>"),
+                    Some(extension)
+                ),
+                MediaKind::Document
+            );
+        }
+    }
+
+    #[test]
+    fn av_containers() {
+        let ogg = b"OggS\0\x02\0\0";
+        assert_eq!(MediaKind::sniff(ogg, None), MediaKind::Audio);
+        assert_eq!(MediaKind::sniff(ogg, Some("ogg")), MediaKind::Audio);
+        assert_eq!(MediaKind::sniff(ogg, Some("ogv")), MediaKind::Video);
+
+        let matroska = b"\x1A\x45\xDF\xA3\x01";
+        assert_eq!(MediaKind::sniff(matroska, None), MediaKind::Video);
+        assert_eq!(MediaKind::sniff(matroska, Some("webm")), MediaKind::Video);
+        assert_eq!(MediaKind::sniff(matroska, Some("MKA")), MediaKind::Audio);
+
+        assert_eq!(MediaKind::sniff(b"", Some("ogv")), MediaKind::Video);
+        assert_eq!(MediaKind::sniff(b"", Some("mka")), MediaKind::Audio);
+    }
+
+    #[test]
+    fn magic_bytes_take_priority_over_extension() {
+        assert_eq!(
+            MediaKind::sniff(&gif(&[GIF_LOOP, GIF_IMAGE]), Some("png")),
+            MediaKind::Animation
+        );
+        assert_eq!(
+            MediaKind::sniff(&zip_with_stored_entry(b"notes.txt", b"hi"), Some("txt")),
+            MediaKind::Archive
+        );
+        assert_eq!(
+            MediaKind::sniff(&zip_with_stored_entry(b"report.pdf", b""), Some("pdf")),
+            MediaKind::Archive
+        );
+        assert_eq!(
+            MediaKind::sniff(b"\x1A\x45\xDF\xA3\x01", Some("mp3")),
+            MediaKind::Video
+        );
+        assert_eq!(
+            MediaKind::sniff(b"%PDF-1.7", Some("zip")),
+            MediaKind::Document
+        );
+    }
+
+    #[test]
+    fn falls_back_to_extension() {
+        assert_eq!(MediaKind::sniff(b"", Some(".MKV")), MediaKind::Video);
+        assert_eq!(
+            MediaKind::sniff(b"plain text", Some("md")),
+            MediaKind::Document
+        );
+        assert_eq!(
+            MediaKind::sniff(b"plain text", Some("xyz")),
+            MediaKind::Other
+        );
+        assert_eq!(MediaKind::sniff(b"plain text", None), MediaKind::Other);
+    }
+}
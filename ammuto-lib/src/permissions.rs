@@ -0,0 +1,220 @@
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Sub, SubAssign};
+
+/// A set of named permission bits granted to a user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Permissions(u64);
+
+impl Permissions {
+    pub const NONE: Permissions = Permissions(0);
+    pub const READ_MEDIA: Permissions = Permissions(1 << 0);
+    pub const WRITE_MEDIA: Permissions = Permissions(1 << 1);
+    pub const DELETE_MEDIA: Permissions = Permissions(1 << 2);
+    pub const READ_TAGS: Permissions = Permissions(1 << 3);
+    pub const WRITE_TAGS: Permissions = Permissions(1 << 4);
+    pub const READ_COLLECTIONS: Permissions = Permissions(1 << 5);
+    pub const WRITE_COLLECTIONS: Permissions = Permissions(1 << 6);
+    pub const MANAGE_USERS: Permissions = Permissions(1 << 7);
+    pub const ALL: Permissions = Permissions(
+        Self::READ_MEDIA.0
+            | Self::WRITE_MEDIA.0
+            | Self::DELETE_MEDIA.0
+            | Self::READ_TAGS.0
+            | Self::WRITE_TAGS.0
+            | Self::READ_COLLECTIONS.0
+            | Self::WRITE_COLLECTIONS.0
+            | Self::MANAGE_USERS.0,
+    );
+
+    /// Creates a permission set from raw bits, or `None` if any of them aren't named
+    pub const fn from_bits(bits: u64) -> Option<Permissions> {
+        if bits & !Self::ALL.0 == 0 {
+            Some(Permissions(bits))
+        } else {
+            None
+        }
+    }
+
+    /// Creates a permission set from raw bits, keeping any bits that aren't named.
+    /// Use this when reading stored permissions, so grants defined by newer versions aren't lost
+    pub const fn from_bits_retain(bits: u64) -> Permissions {
+        Permissions(bits)
+    }
+
+    /// Creates a permission set from raw bits, discarding any bits that aren't named
+    pub const fn from_bits_truncate(bits: u64) -> Permissions {
+        Permissions(bits & Self::ALL.0)
+    }
+
+    /// The raw bits of this permission set, including any unnamed bits, for storage
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    /// Whether every permission in `required` is granted by this set
+    pub const fn allows(self, required: Permissions) -> bool {
+        self.0 & required.0 == required.0
+    }
+
+    /// Adds the given permissions to this set
+    pub fn grant(&mut self, permissions: Permissions) {
+        self.0 |= permissions.0;
+    }
+
+    /// Removes the given permissions from this set
+    pub fn revoke(&mut self, permissions: Permissions) {
+        self.0 &= !permissions.0;
+    }
+}
+
+impl BitOr for Permissions {
+    type Output = Permissions;
+
+    fn bitor(self, rhs: Permissions) -> Permissions {
+        Permissions(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Permissions {
+    fn bitor_assign(&mut self, rhs: Permissions) {
+        self.grant(rhs);
+    }
+}
+
+impl BitAnd for Permissions {
+    type Output = Permissions;
+
+    fn bitand(self, rhs: Permissions) -> Permissions {
+        Permissions(self.0 & rhs.0)
+    }
+}
+
+impl BitAndAssign for Permissions {
+    fn bitand_assign(&mut self, rhs: Permissions) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl Sub for Permissions {
+    type Output = Permissions;
+
+    fn sub(self, rhs: Permissions) -> Permissions {
+        Permissions(self.0 & !rhs.0)
+    }
+}
+
+impl SubAssign for Permissions {
+    fn sub_assign(&mut self, rhs: Permissions) {
+        self.revoke(rhs);
+    }
+}
+
+impl Not for Permissions {
+    type Output = Permissions;
+
+    /// Every named permission not in this set
+    fn not(self) -> Permissions {
+        Permissions::from_bits_truncate(!self.0)
+    }
+}
+
+/// Serialized as the raw bits, with unnamed bits kept on the way back in
+#[cfg(feature = "serde")]
+impl serde::Serialize for Permissions {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Permissions {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Permissions, D::Error> {
+        u64::deserialize(deserializer).map(Permissions::from_bits_retain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grant_and_revoke() {
+        let mut permissions = Permissions::READ_MEDIA;
+        assert!(!permissions.allows(Permissions::WRITE_TAGS));
+
+        permissions.grant(Permissions::WRITE_TAGS);
+        assert!(permissions.allows(Permissions::READ_MEDIA | Permissions::WRITE_TAGS));
+
+        permissions.revoke(Permissions::READ_MEDIA);
+        assert!(!permissions.allows(Permissions::READ_MEDIA));
+        assert!(permissions.allows(Permissions::WRITE_TAGS));
+    }
+
+    #[test]
+    fn allows_requires_every_bit() {
+        let permissions = Permissions::READ_MEDIA | Permissions::READ_TAGS;
+        assert!(!permissions.allows(Permissions::READ_MEDIA | Permissions::MANAGE_USERS));
+        assert!(permissions.allows(Permissions::NONE));
+        assert!(Permissions::ALL.allows(permissions));
+    }
+
+    #[test]
+    fn operators() {
+        let permissions = Permissions::READ_MEDIA | Permissions::WRITE_TAGS;
+        assert_eq!(
+            permissions & (Permissions::WRITE_TAGS | Permissions::MANAGE_USERS),
+            Permissions::WRITE_TAGS
+        );
+        assert_eq!(
+            permissions - Permissions::WRITE_TAGS,
+            Permissions::READ_MEDIA
+        );
+        assert_eq!(!Permissions::ALL, Permissions::NONE);
+        assert_eq!(!permissions & permissions, Permissions::NONE);
+
+        let mut assigned = permissions;
+        assigned -= Permissions::READ_MEDIA;
+        assigned |= Permissions::MANAGE_USERS;
+        assigned &= Permissions::MANAGE_USERS | Permissions::READ_TAGS;
+        assert_eq!(assigned, Permissions::MANAGE_USERS);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let permissions = Permissions::READ_MEDIA | Permissions::MANAGE_USERS;
+        let json = serde_json::to_string(&permissions).unwrap();
+        assert_eq!(json, permissions.bits().to_string());
+        assert_eq!(
+            serde_json::from_str::<Permissions>(&json).unwrap(),
+            permissions
+        );
+
+        // Bits named by a newer version survive the round trip
+        let future = Permissions::from_bits_retain(1 << 8) | Permissions::READ_MEDIA;
+        let json = serde_json::to_string(&future).unwrap();
+        assert_eq!(serde_json::from_str::<Permissions>(&json).unwrap(), future);
+    }
+
+    #[test]
+    fn unknown_bits_are_checked_or_retained() {
+        assert_eq!(Permissions::from_bits(u64::MAX), None);
+        assert_eq!(
+            Permissions::from_bits(Permissions::ALL.bits()),
+            Some(Permissions::ALL)
+        );
+
+        let retained = Permissions::from_bits_retain(u64::MAX);
+        assert_eq!(retained.bits(), u64::MAX);
+        assert!(retained.allows(Permissions::ALL));
+    }
+
+    #[test]
+    fn unknown_bits_are_truncated() {
+        let permissions = Permissions::from_bits_truncate(u64::MAX);
+        assert_eq!(permissions, Permissions::ALL);
+        assert_eq!(
+            Permissions::from_bits_truncate(permissions.bits()),
+            permissions
+        );
+    }
+}